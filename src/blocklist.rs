@@ -0,0 +1,165 @@
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::sync::OnceLock;
+
+static EXCLUDE: &str = "exclude";
+static EXCLUDE_READS: &str = "exclude-reads";
+static EXCLUDE_WRITES: &str = "exclude-writes";
+
+/// Environment variable pointing at a blocklist policy file. When set, the
+/// process-wide default blocklist is parsed from it the first time it is needed.
+static BLOCKLIST_ENV: &str = "BLURZ_BLOCKLIST";
+
+/// How a blocklisted UUID is restricted.
+///
+/// Mirrors the Web Bluetooth blocklist model from servo's
+/// `bluetooth_traits::blocklist`: a UUID may be excluded entirely, or only for
+/// reads or only for writes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Exclusion {
+    /// The UUID must never be surfaced at all.
+    Exclude,
+    /// Reads of the UUID must be suppressed.
+    ExcludeReads,
+    /// Writes to the UUID must be suppressed.
+    ExcludeWrites,
+}
+
+/// A set of GATT service/characteristic UUIDs that must not be exposed to
+/// untrusted callers, each tagged with how it is restricted.
+#[derive(Clone, Debug, Default)]
+pub struct Blocklist {
+    entries: HashMap<String, Exclusion>,
+}
+
+impl Blocklist {
+    pub fn new() -> Blocklist {
+        Blocklist {
+            entries: HashMap::new(),
+        }
+    }
+
+    /// Parse a blocklist from the simple text format: one UUID per line,
+    /// followed by an optional exclusion flag (`exclude`, `exclude-reads` or
+    /// `exclude-writes`, defaulting to `exclude`). Blank lines and lines
+    /// starting with `#` are ignored.
+    pub fn parse(data: &str) -> Blocklist {
+        let mut entries = HashMap::new();
+
+        for line in data.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut fields = line.split_whitespace();
+            let uuid = match fields.next() {
+                Some(uuid) => uuid.to_lowercase(),
+                None => continue,
+            };
+
+            let exclusion = match fields.next() {
+                Some(flag) if flag == EXCLUDE_READS => Exclusion::ExcludeReads,
+                Some(flag) if flag == EXCLUDE_WRITES => Exclusion::ExcludeWrites,
+                Some(flag) if flag == EXCLUDE => Exclusion::Exclude,
+                None => Exclusion::Exclude,
+                Some(_) => continue,
+            };
+
+            entries.insert(uuid, exclusion);
+        }
+
+        Blocklist { entries }
+    }
+
+    /// Whether `uuid` is blocklisted for the given access `which`.
+    ///
+    /// An `Exclude` entry matches every query; a read- or write-specific entry
+    /// matches only the corresponding access.
+    pub fn uuid_is_blocklisted(&self, uuid: &str, which: Exclusion) -> bool {
+        match self.entries.get(&uuid.to_lowercase()) {
+            Some(Exclusion::Exclude) => true,
+            Some(entry) => *entry == which,
+            None => false,
+        }
+    }
+}
+
+/// The process-wide default blocklist, loaded lazily from the file named by the
+/// `BLURZ_BLOCKLIST` environment variable. Deployments ship their own policy by
+/// pointing that variable at a file; if it is unset or unreadable the blocklist
+/// is empty and nothing is filtered.
+pub fn default_blocklist() -> &'static Blocklist {
+    static DEFAULT: OnceLock<Blocklist> = OnceLock::new();
+    DEFAULT.get_or_init(|| match env::var(BLOCKLIST_ENV) {
+        Ok(path) => match fs::read_to_string(path) {
+            Ok(data) => Blocklist::parse(&data),
+            Err(_) => Blocklist::new(),
+        },
+        Err(_) => Blocklist::new(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flags_map_to_exclusions() {
+        let list = Blocklist::parse(
+            "00000000-0000-0000-0000-000000000001\n\
+             00000000-0000-0000-0000-000000000002 exclude\n\
+             00000000-0000-0000-0000-000000000003 exclude-reads\n\
+             00000000-0000-0000-0000-000000000004 exclude-writes\n",
+        );
+
+        assert!(list.uuid_is_blocklisted("00000000-0000-0000-0000-000000000001", Exclusion::Exclude));
+        assert!(list.uuid_is_blocklisted("00000000-0000-0000-0000-000000000002", Exclusion::Exclude));
+        assert!(
+            list.uuid_is_blocklisted("00000000-0000-0000-0000-000000000003", Exclusion::ExcludeReads)
+        );
+        assert!(!list
+            .uuid_is_blocklisted("00000000-0000-0000-0000-000000000003", Exclusion::ExcludeWrites));
+        assert!(list
+            .uuid_is_blocklisted("00000000-0000-0000-0000-000000000004", Exclusion::ExcludeWrites));
+    }
+
+    #[test]
+    fn exclude_matches_every_access() {
+        let list = Blocklist::parse("0000FFFF-0000-0000-0000-000000000000 exclude");
+        let uuid = "0000ffff-0000-0000-0000-000000000000";
+        assert!(list.uuid_is_blocklisted(uuid, Exclusion::Exclude));
+        assert!(list.uuid_is_blocklisted(uuid, Exclusion::ExcludeReads));
+        assert!(list.uuid_is_blocklisted(uuid, Exclusion::ExcludeWrites));
+    }
+
+    #[test]
+    fn queries_are_case_insensitive() {
+        let list = Blocklist::parse("ABCDEF00-0000-0000-0000-000000000000");
+        assert!(list.uuid_is_blocklisted("abcdef00-0000-0000-0000-000000000000", Exclusion::Exclude));
+        assert!(list.uuid_is_blocklisted("ABCDEF00-0000-0000-0000-000000000000", Exclusion::Exclude));
+    }
+
+    #[test]
+    fn comments_blanks_and_unknown_flags_are_skipped() {
+        let list = Blocklist::parse(
+            "# firmware update service\n\
+             \n\
+             00000000-0000-0000-0000-0000000000aa exclude-everything\n\
+             00000000-0000-0000-0000-0000000000bb exclude-reads\n",
+        );
+
+        // Unknown flag => entry rejected entirely.
+        assert!(!list
+            .uuid_is_blocklisted("00000000-0000-0000-0000-0000000000aa", Exclusion::Exclude));
+        assert!(list
+            .uuid_is_blocklisted("00000000-0000-0000-0000-0000000000bb", Exclusion::ExcludeReads));
+    }
+
+    #[test]
+    fn unlisted_uuid_is_not_blocklisted() {
+        let list = Blocklist::parse("00000000-0000-0000-0000-000000000001 exclude");
+        assert!(!list.uuid_is_blocklisted("ffffffff-ffff-ffff-ffff-ffffffffffff", Exclusion::Exclude));
+    }
+}
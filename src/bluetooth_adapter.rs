@@ -2,25 +2,61 @@ use crate::bluetooth_device::BluetoothDevice;
 use crate::bluetooth_le_advertising_data::BluetoothAdvertisingData;
 use crate::bluetooth_session::BluetoothSession;
 use crate::bluetooth_utils;
+use crate::blocklist::{self, Exclusion};
 use crate::ok_or_str;
-use dbus::arg::messageitem::MessageItem;
+use dbus::arg::messageitem::{MessageItem, MessageItemArray};
 use dbus::Message;
 use hex::FromHex;
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::error::Error;
+use std::rc::Rc;
 
 static ADAPTER_INTERFACE: &str = "org.bluez.Adapter1";
+static DEVICE_INTERFACE: &str = "org.bluez.Device1";
+static AGENT_MANAGER_INTERFACE: &str = "org.bluez.AgentManager1";
+static AGENT_MANAGER_PATH: &str = "/org/bluez";
+
+/// Backend that actually services a `BluetoothAdapter`'s property and method
+/// calls. The real implementation talks to BlueZ over D-Bus; the mock keeps the
+/// state in memory so adapter logic can be exercised without hardware or a
+/// running D-Bus daemon.
+pub trait AdapterBackend: std::fmt::Debug {
+    fn get_property(&self, prop: &str) -> Result<MessageItem, Box<dyn Error>>;
+    fn set_property(
+        &self,
+        prop: &str,
+        value: MessageItem,
+        timeout_ms: i32,
+    ) -> Result<(), Box<dyn Error>>;
+    fn call_method(
+        &self,
+        method: &str,
+        param: Option<&[MessageItem]>,
+        timeout_ms: i32,
+    ) -> Result<Message, Box<dyn Error>>;
+    fn get_device_list(&self) -> Result<Vec<String>, Box<dyn Error>>;
+
+    /// The session backing this adapter, if any. The mock backend has none.
+    fn session(&self) -> Option<&BluetoothSession> {
+        None
+    }
+}
 
 #[derive(Clone, Debug)]
 pub struct BluetoothAdapter<'a> {
     object_path: String,
-    session: &'a BluetoothSession,
+    backend: Rc<dyn AdapterBackend + 'a>,
 }
 
 impl<'a> BluetoothAdapter<'a> {
     fn new(session: &'a BluetoothSession, object_path: &str) -> BluetoothAdapter<'a> {
         BluetoothAdapter {
             object_path: object_path.to_string(),
-            session,
+            backend: Rc::new(DBusAdapterBackend {
+                session,
+                object_path: object_path.to_string(),
+            }),
         }
     }
 
@@ -48,55 +84,66 @@ impl<'a> BluetoothAdapter<'a> {
         Err(Box::from("Bluetooth adapter not found"))
     }
 
+    /// Create an adapter backed by the in-memory mock, seeded with the given
+    /// state. This lets callers (and this crate's own tests) drive discovery,
+    /// connection and `BluetoothEvent` flows deterministically.
+    pub fn init_mock(
+        object_path: &str,
+        address: &str,
+        name: &str,
+        devices: Vec<String>,
+    ) -> BluetoothAdapter<'static> {
+        let backend = MockAdapterBackend::new(object_path, address, name, devices);
+        BluetoothAdapter {
+            object_path: object_path.to_string(),
+            backend: Rc::new(backend),
+        }
+    }
+
     pub fn get_id(&self) -> String {
         self.object_path.clone()
     }
 
+    /// Returns a handle to the first known device.
+    ///
+    /// A `BluetoothDevice` always talks to BlueZ over a live `BluetoothSession`,
+    /// so this requires a real backend and returns `"No session available."` on
+    /// a mock adapter. Tests drive the device set through
+    /// [`get_device_list`](Self::get_device_list) (and mutate it via
+    /// [`remove_device`](Self::remove_device)) instead.
     pub fn get_first_device(&self) -> Result<BluetoothDevice, Box<dyn Error>> {
-        let devices =
-            bluetooth_utils::list_devices(self.session.get_connection(), &self.object_path)?;
+        let session = ok_or_str!(self.backend.session().ok_or("No session available."))?;
+        let devices = self.backend.get_device_list()?;
 
         if devices.is_empty() {
             return Err(Box::from("No device found."));
         }
-        Ok(BluetoothDevice::new(self.session, &devices[0]))
+        Ok(BluetoothDevice::new(session, &devices[0]))
     }
 
     pub fn get_addata(&self) -> Result<BluetoothAdvertisingData, Box<dyn Error>> {
-        let addata =
-            bluetooth_utils::list_addata_1(self.session.get_connection(), &self.object_path)?;
+        let session = ok_or_str!(self.backend.session().ok_or("No session available."))?;
+        let addata = bluetooth_utils::list_addata_1(session.get_connection(), &self.object_path)?;
 
         if addata.is_empty() {
             return Err(Box::from("No addata found."));
         }
-        Ok(BluetoothAdvertisingData::new(&self.session, &addata[0]))
+        Ok(BluetoothAdvertisingData::new(session, &addata[0]))
     }
 
     pub fn get_device_list(&self) -> Result<Vec<String>, Box<dyn Error>> {
-        bluetooth_utils::list_devices(self.session.get_connection(), &self.object_path)
+        self.backend.get_device_list()
     }
 
     fn get_property(&self, prop: &str) -> Result<MessageItem, Box<dyn Error>> {
-        bluetooth_utils::get_property(
-            self.session.get_connection(),
-            ADAPTER_INTERFACE,
-            &self.object_path,
-            prop,
-        )
+        self.backend.get_property(prop)
     }
 
     fn set_property<T>(&self, prop: &str, value: T, timeout_ms: i32) -> Result<(), Box<dyn Error>>
     where
         T: Into<MessageItem>,
     {
-        bluetooth_utils::set_property(
-            self.session.get_connection(),
-            ADAPTER_INTERFACE,
-            &self.object_path,
-            prop,
-            value,
-            timeout_ms,
-        )
+        self.backend.set_property(prop, value.into(), timeout_ms)
     }
 
     fn call_method(
@@ -105,14 +152,7 @@ impl<'a> BluetoothAdapter<'a> {
         param: Option<&[MessageItem]>,
         timeout_ms: i32,
     ) -> Result<Message, Box<dyn Error>> {
-        bluetooth_utils::call_method(
-            self.session.get_connection(),
-            ADAPTER_INTERFACE,
-            &self.object_path,
-            method,
-            param,
-            timeout_ms,
-        )
+        self.backend.call_method(method, param, timeout_ms)
     }
 
     /*
@@ -213,9 +253,14 @@ impl<'a> BluetoothAdapter<'a> {
     pub fn get_uuids(&self) -> Result<Vec<String>, Box<dyn Error>> {
         let uuids = self.get_property("UUIDs")?;
         let z: &[MessageItem] = ok_or_str!(uuids.inner())?;
+        let blocklist = blocklist::default_blocklist();
         let mut v: Vec<String> = Vec::new();
         for y in z {
-            v.push(String::from(ok_or_str!(y.inner::<&str>())?));
+            let uuid = String::from(ok_or_str!(y.inner::<&str>())?);
+            if blocklist.uuid_is_blocklisted(&uuid, Exclusion::Exclude) {
+                continue;
+            }
+            v.push(uuid);
         }
         Ok(v)
     }
@@ -306,9 +351,327 @@ impl<'a> BluetoothAdapter<'a> {
 
         self.call_method("ConnectDevice", Some(&[m]), timeout_ms)
     }
+
+    // http://git.kernel.org/cgit/bluetooth/bluez.git/tree/doc/adapter-api.txt#n55
+    pub fn set_discovery_filter(
+        &self,
+        uuids: Vec<String>,
+        rssi: Option<i16>,
+        pathloss: Option<u16>,
+        transport: Option<Transport>,
+        duplicate_data: Option<bool>,
+        discoverable: Option<bool>,
+        timeout_ms: i32,
+    ) -> Result<Message, Box<dyn Error>> {
+        let mut filter: Vec<(MessageItem, MessageItem)> = Vec::new();
+
+        if !uuids.is_empty() {
+            let uuids: Vec<MessageItem> = uuids.iter().map(|u| u.as_str().into()).collect();
+            filter.push((
+                "UUIDs".into(),
+                MessageItem::Variant(Box::new(ok_or_str!(MessageItem::new_array(uuids))?)),
+            ));
+        }
+
+        if let Some(rssi) = rssi {
+            filter.push(("RSSI".into(), MessageItem::Variant(Box::new(rssi.into()))));
+        }
+
+        if let Some(pathloss) = pathloss {
+            filter.push((
+                "Pathloss".into(),
+                MessageItem::Variant(Box::new(pathloss.into())),
+            ));
+        }
+
+        if let Some(transport) = transport {
+            let transport = match transport {
+                Transport::Auto => "auto",
+                Transport::BrEdr => "bredr",
+                Transport::Le => "le",
+            };
+            filter.push((
+                "Transport".into(),
+                MessageItem::Variant(Box::new(transport.into())),
+            ));
+        }
+
+        if let Some(duplicate_data) = duplicate_data {
+            filter.push((
+                "DuplicateData".into(),
+                MessageItem::Variant(Box::new(duplicate_data.into())),
+            ));
+        }
+
+        if let Some(discoverable) = discoverable {
+            filter.push((
+                "Discoverable".into(),
+                MessageItem::Variant(Box::new(discoverable.into())),
+            ));
+        }
+
+        let m = ok_or_str!(MessageItem::new_dict(filter))?;
+        self.call_method("SetDiscoveryFilter", Some(&[m]), timeout_ms)
+    }
+
+    fn call_device_method(
+        &self,
+        device: &str,
+        method: &str,
+        param: Option<&[MessageItem]>,
+        timeout_ms: i32,
+    ) -> Result<Message, Box<dyn Error>> {
+        let session = ok_or_str!(self.backend.session().ok_or("No session available."))?;
+        bluetooth_utils::call_method(
+            session.get_connection(),
+            DEVICE_INTERFACE,
+            device,
+            method,
+            param,
+            timeout_ms,
+        )
+    }
+
+    // http://git.kernel.org/cgit/bluetooth/bluez.git/tree/doc/device-api.txt#n12
+    pub fn pair(&self, device: &str, timeout_ms: i32) -> Result<Message, Box<dyn Error>> {
+        self.call_device_method(device, "Pair", None, timeout_ms)
+    }
+
+    // http://git.kernel.org/cgit/bluetooth/bluez.git/tree/doc/device-api.txt#n35
+    pub fn cancel_pairing(&self, device: &str, timeout_ms: i32) -> Result<(), Box<dyn Error>> {
+        self.call_device_method(device, "CancelPairing", None, timeout_ms)?;
+        Ok(())
+    }
+
+    // http://git.kernel.org/cgit/bluetooth/bluez.git/tree/doc/agent-api.txt#n186
+    pub fn set_pairing_agent(
+        &self,
+        agent: &str,
+        capability: &str,
+        timeout_ms: i32,
+    ) -> Result<(), Box<dyn Error>> {
+        let session = ok_or_str!(self.backend.session().ok_or("No session available."))?;
+        bluetooth_utils::call_method(
+            session.get_connection(),
+            AGENT_MANAGER_INTERFACE,
+            AGENT_MANAGER_PATH,
+            "RegisterAgent",
+            Some(&[
+                MessageItem::ObjectPath(agent.to_string().into()),
+                capability.into(),
+            ]),
+            timeout_ms,
+        )?;
+        bluetooth_utils::call_method(
+            session.get_connection(),
+            AGENT_MANAGER_INTERFACE,
+            AGENT_MANAGER_PATH,
+            "RequestDefaultAgent",
+            Some(&[MessageItem::ObjectPath(agent.to_string().into())]),
+            timeout_ms,
+        )?;
+        Ok(())
+    }
 }
 
 pub enum AddressType {
     Public,
     Random,
 }
+
+pub enum Transport {
+    Auto,
+    BrEdr,
+    Le,
+}
+
+/// The real backend: every call is forwarded to BlueZ over the session's D-Bus
+/// connection, exactly as the adapter used to do inline.
+#[derive(Clone, Debug)]
+struct DBusAdapterBackend<'a> {
+    session: &'a BluetoothSession,
+    object_path: String,
+}
+
+impl<'a> AdapterBackend for DBusAdapterBackend<'a> {
+    fn get_property(&self, prop: &str) -> Result<MessageItem, Box<dyn Error>> {
+        bluetooth_utils::get_property(
+            self.session.get_connection(),
+            ADAPTER_INTERFACE,
+            &self.object_path,
+            prop,
+        )
+    }
+
+    fn set_property(
+        &self,
+        prop: &str,
+        value: MessageItem,
+        timeout_ms: i32,
+    ) -> Result<(), Box<dyn Error>> {
+        bluetooth_utils::set_property(
+            self.session.get_connection(),
+            ADAPTER_INTERFACE,
+            &self.object_path,
+            prop,
+            value,
+            timeout_ms,
+        )
+    }
+
+    fn call_method(
+        &self,
+        method: &str,
+        param: Option<&[MessageItem]>,
+        timeout_ms: i32,
+    ) -> Result<Message, Box<dyn Error>> {
+        bluetooth_utils::call_method(
+            self.session.get_connection(),
+            ADAPTER_INTERFACE,
+            &self.object_path,
+            method,
+            param,
+            timeout_ms,
+        )
+    }
+
+    fn get_device_list(&self) -> Result<Vec<String>, Box<dyn Error>> {
+        bluetooth_utils::list_devices(self.session.get_connection(), &self.object_path)
+    }
+
+    fn session(&self) -> Option<&BluetoothSession> {
+        Some(self.session)
+    }
+}
+
+/// In-memory backend used by `BluetoothAdapter::init_mock`.
+#[derive(Debug)]
+struct MockAdapterBackend {
+    object_path: String,
+    properties: RefCell<HashMap<String, MessageItem>>,
+    devices: RefCell<Vec<String>>,
+}
+
+impl MockAdapterBackend {
+    fn new(object_path: &str, address: &str, name: &str, devices: Vec<String>) -> MockAdapterBackend {
+        let mut properties: HashMap<String, MessageItem> = HashMap::new();
+        properties.insert("Address".into(), address.into());
+        properties.insert("Name".into(), name.into());
+        properties.insert("Alias".into(), name.into());
+        properties.insert("Powered".into(), true.into());
+        properties.insert("Discoverable".into(), false.into());
+        properties.insert("Pairable".into(), false.into());
+        properties.insert("Discovering".into(), false.into());
+        properties.insert("Class".into(), 0u32.into());
+        properties.insert("PairableTimeout".into(), 0u32.into());
+        properties.insert("DiscoverableTimeout".into(), 0u32.into());
+        properties.insert(
+            "UUIDs".into(),
+            MessageItem::Array(
+                MessageItemArray::new(Vec::new(), "as".into()).expect("valid empty string array"),
+            ),
+        );
+
+        MockAdapterBackend {
+            object_path: object_path.to_string(),
+            properties: RefCell::new(properties),
+            devices: RefCell::new(devices),
+        }
+    }
+}
+
+impl AdapterBackend for MockAdapterBackend {
+    fn get_property(&self, prop: &str) -> Result<MessageItem, Box<dyn Error>> {
+        self.properties
+            .borrow()
+            .get(prop)
+            .cloned()
+            .ok_or_else(|| Box::from(format!("No such property: {}", prop)))
+    }
+
+    fn set_property(
+        &self,
+        prop: &str,
+        value: MessageItem,
+        _timeout_ms: i32,
+    ) -> Result<(), Box<dyn Error>> {
+        self.properties.borrow_mut().insert(prop.to_string(), value);
+        Ok(())
+    }
+
+    fn call_method(
+        &self,
+        method: &str,
+        param: Option<&[MessageItem]>,
+        _timeout_ms: i32,
+    ) -> Result<Message, Box<dyn Error>> {
+        if method == "RemoveDevice" {
+            if let Some([MessageItem::ObjectPath(device)]) = param {
+                let device = device.to_string();
+                self.devices.borrow_mut().retain(|d| *d != device);
+            }
+        }
+
+        // The mock has no connection to produce a reply on, so hand back a
+        // synthesized signal standing in for the method return.
+        let path = if self.object_path.is_empty() {
+            "/"
+        } else {
+            &self.object_path
+        };
+        let message = Message::new_signal(path, ADAPTER_INTERFACE, method)?;
+        Ok(message)
+    }
+
+    fn get_device_list(&self) -> Result<Vec<String>, Box<dyn Error>> {
+        Ok(self.devices.borrow().clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mock() -> BluetoothAdapter<'static> {
+        BluetoothAdapter::init_mock(
+            "/org/bluez/hci0",
+            "00:11:22:33:44:55",
+            "hci0",
+            vec![
+                "/org/bluez/hci0/dev_AA_BB_CC_DD_EE_FF".to_string(),
+                "/org/bluez/hci0/dev_11_22_33_44_55_66".to_string(),
+            ],
+        )
+    }
+
+    #[test]
+    fn seeded_properties_are_readable() {
+        let adapter = mock();
+        assert_eq!(adapter.get_id(), "/org/bluez/hci0");
+        assert_eq!(adapter.get_address().unwrap(), "00:11:22:33:44:55");
+        assert_eq!(adapter.get_name().unwrap(), "hci0");
+        assert!(adapter.is_powered().unwrap());
+        assert!(!adapter.is_discovering().unwrap());
+        assert!(adapter.get_uuids().unwrap().is_empty());
+    }
+
+    #[test]
+    fn property_setters_round_trip() {
+        let adapter = mock();
+        adapter.set_powered(false).unwrap();
+        assert!(!adapter.is_powered().unwrap());
+        adapter.set_alias("living-room").unwrap();
+        assert_eq!(adapter.get_alias().unwrap(), "living-room");
+    }
+
+    #[test]
+    fn remove_device_updates_the_device_list() {
+        let adapter = mock();
+        assert_eq!(adapter.get_device_list().unwrap().len(), 2);
+        adapter
+            .remove_device("/org/bluez/hci0/dev_AA_BB_CC_DD_EE_FF")
+            .unwrap();
+        let devices = adapter.get_device_list().unwrap();
+        assert_eq!(devices, vec!["/org/bluez/hci0/dev_11_22_33_44_55_66"]);
+    }
+}
@@ -1,3 +1,4 @@
+use crate::blocklist::{default_blocklist, Exclusion};
 use dbus::{arg::cast, arg::RefArg, arg::TypeMismatchError, arg::Variant, Message};
 use std::collections::HashMap;
 
@@ -19,6 +20,10 @@ pub enum BluetoothEvent {
         object_path: String,
         services_resolved: bool,
     },
+    Paired {
+        object_path: String,
+        paired: bool,
+    },
     Value {
         object_path: String,
         value: Box<[u8]>,
@@ -27,11 +32,61 @@ pub enum BluetoothEvent {
         object_path: String,
         rssi: i16,
     },
+    ManufacturerData {
+        object_path: String,
+        data: HashMap<u16, Box<[u8]>>,
+    },
+    ServiceData {
+        object_path: String,
+        data: HashMap<String, Box<[u8]>>,
+    },
     None,
 }
 
+// Extract a byte array out of the value side of an advertising-data map,
+// descending through the `a{sv}`/`a{qv}` variant wrapper as needed.
+fn extract_bytes(arg: &dyn RefArg) -> Option<Box<[u8]>> {
+    if let Some(bytes) = cast::<Vec<u8>>(arg) {
+        return Some(bytes.clone().into_boxed_slice());
+    }
+
+    let inner = arg.as_iter()?.next()?;
+    if let Some(bytes) = cast::<Vec<u8>>(inner) {
+        return Some(bytes.clone().into_boxed_slice());
+    }
+
+    let mut out: Vec<u8> = Vec::new();
+    for b in inner.as_iter()? {
+        out.push(b.as_u64()? as u8);
+    }
+    Some(out.into_boxed_slice())
+}
+
 impl BluetoothEvent {
+    /// Decode a `PropertiesChanged` message into a `BluetoothEvent`.
+    ///
+    /// This is deliberately *unfiltered*: it performs no blocklist suppression,
+    /// because the message carries only the object path and not the UUID the
+    /// blocklist is keyed by. Callers that need `Value` reads of
+    /// read-blocklisted characteristics suppressed must use
+    /// [`from_with_resolver`](Self::from_with_resolver) — which the push-style
+    /// `BluetoothEventStream::with_resolver` does for them.
     pub fn from(conn_msg: Message) -> Option<BluetoothEvent> {
+        BluetoothEvent::from_with_resolver(conn_msg, |_| None)
+    }
+
+    /// Like [`from`](Self::from), but consults the default blocklist to suppress
+    /// `Value` events for read-blocklisted characteristics.
+    ///
+    /// The `PropertiesChanged`/`Value` payload carries only the characteristic's
+    /// D-Bus object path, never its UUID, so the caller must supply a
+    /// `resolve_uuid` closure mapping that path to the characteristic UUID (e.g.
+    /// via a GATT lookup). A suppressed read is reported as
+    /// `BluetoothEvent::None`.
+    pub fn from_with_resolver<F>(conn_msg: Message, resolve_uuid: F) -> Option<BluetoothEvent>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
         let result: Result<(&str, HashMap<String, Variant<Box<dyn RefArg>>>), TypeMismatchError> =
             conn_msg.read2();
 
@@ -83,8 +138,31 @@ impl BluetoothEvent {
                     }
                 }
 
+                if let Some(value) = properties.get("Paired") {
+                    if let Some(paired) = cast::<bool>(&value.0) {
+                        let event = BluetoothEvent::Paired {
+                            object_path,
+                            paired: *paired,
+                        };
+
+                        return Some(event);
+                    }
+                }
+
                 if let Some(value) = properties.get("Value") {
                     if let Some(value) = cast::<Vec<u8>>(&value.0) {
+                        // Suppress reads from characteristics that policy has
+                        // blocklisted, so untrusted callers never see them. The
+                        // event only carries the object path, so resolve it to a
+                        // UUID before querying the (UUID-keyed) blocklist.
+                        if let Some(uuid) = resolve_uuid(&object_path) {
+                            if default_blocklist()
+                                .uuid_is_blocklisted(&uuid, Exclusion::ExcludeReads)
+                            {
+                                return Some(BluetoothEvent::None);
+                            }
+                        }
+
                         let event = BluetoothEvent::Value {
                             object_path,
                             value: value.clone().into_boxed_slice(),
@@ -105,6 +183,36 @@ impl BluetoothEvent {
                     }
                 }
 
+                if let Some(value) = properties.get("ManufacturerData") {
+                    if let Some(mut iter) = value.0.as_iter() {
+                        let mut data: HashMap<u16, Box<[u8]>> = HashMap::new();
+                        while let (Some(key), Some(val)) = (iter.next(), iter.next()) {
+                            if let (Some(id), Some(bytes)) = (key.as_u64(), extract_bytes(val)) {
+                                data.insert(id as u16, bytes);
+                            }
+                        }
+
+                        if !data.is_empty() {
+                            return Some(BluetoothEvent::ManufacturerData { object_path, data });
+                        }
+                    }
+                }
+
+                if let Some(value) = properties.get("ServiceData") {
+                    if let Some(mut iter) = value.0.as_iter() {
+                        let mut data: HashMap<String, Box<[u8]>> = HashMap::new();
+                        while let (Some(key), Some(val)) = (iter.next(), iter.next()) {
+                            if let (Some(uuid), Some(bytes)) = (key.as_str(), extract_bytes(val)) {
+                                data.insert(uuid.to_string(), bytes);
+                            }
+                        }
+
+                        if !data.is_empty() {
+                            return Some(BluetoothEvent::ServiceData { object_path, data });
+                        }
+                    }
+                }
+
                 Some(BluetoothEvent::None)
             }
             Err(_err) => None,
@@ -0,0 +1,146 @@
+use crate::bluetooth_event::BluetoothEvent;
+use crate::bluetooth_session::BluetoothSession;
+use dbus::ffidisp::ConnectionItem;
+use std::error::Error;
+
+static PROPERTIES_CHANGED_RULE: &str =
+    "type='signal',interface='org.freedesktop.DBus.Properties',member='PropertiesChanged'";
+
+/// A push-style view over `PropertiesChanged` signals, layered on top of the
+/// low-level `BluetoothEvent::from` helper.
+///
+/// The stream registers the `PropertiesChanged` match rule on the session
+/// connection when it is created and removes it again on drop. Iterating it
+/// yields decoded `BluetoothEvent`s, skipping `BluetoothEvent::None`, so callers
+/// never have to drive the D-Bus message pump themselves.
+pub struct BluetoothEventStream<'a> {
+    session: &'a BluetoothSession,
+    object_path: Option<String>,
+    resolve_uuid: Box<dyn Fn(&str) -> Option<String> + Send + 'a>,
+}
+
+impl<'a> BluetoothEventStream<'a> {
+    /// Watch every object on the connection.
+    pub fn new(session: &'a BluetoothSession) -> Result<BluetoothEventStream<'a>, Box<dyn Error>> {
+        BluetoothEventStream::with_path(session, None)
+    }
+
+    /// Watch only signals emitted for `object_path` (e.g. a single
+    /// `BluetoothAdapter` or `BluetoothDevice`).
+    pub fn for_object_path(
+        session: &'a BluetoothSession,
+        object_path: &str,
+    ) -> Result<BluetoothEventStream<'a>, Box<dyn Error>> {
+        BluetoothEventStream::with_path(session, Some(object_path.to_string()))
+    }
+
+    /// Attach a characteristic path → UUID resolver so that `Value` reads of
+    /// read-blocklisted characteristics are suppressed as they flow through the
+    /// stream (see [`BluetoothEvent::from_with_resolver`]). Without a resolver
+    /// the stream, like the plain `BluetoothEvent::from`, cannot know a
+    /// characteristic's UUID and so performs no blocklist filtering.
+    pub fn with_resolver<F>(mut self, resolve_uuid: F) -> BluetoothEventStream<'a>
+    where
+        F: Fn(&str) -> Option<String> + Send + 'a,
+    {
+        self.resolve_uuid = Box::new(resolve_uuid);
+        self
+    }
+
+    fn with_path(
+        session: &'a BluetoothSession,
+        object_path: Option<String>,
+    ) -> Result<BluetoothEventStream<'a>, Box<dyn Error>> {
+        session.get_connection().add_match(PROPERTIES_CHANGED_RULE)?;
+        Ok(BluetoothEventStream {
+            session,
+            object_path,
+            resolve_uuid: Box::new(|_| None),
+        })
+    }
+
+    fn matches_path(&self, event_path: Option<&str>) -> bool {
+        match self.object_path {
+            Some(ref path) => event_path == Some(path.as_str()),
+            None => true,
+        }
+    }
+}
+
+impl<'a> Iterator for BluetoothEventStream<'a> {
+    type Item = BluetoothEvent;
+
+    fn next(&mut self) -> Option<BluetoothEvent> {
+        loop {
+            for item in self.session.get_connection().incoming(1000) {
+                if let ConnectionItem::Signal(message) = item {
+                    let path = message.path().map(|p| p.to_string());
+                    if !self.matches_path(path.as_deref()) {
+                        continue;
+                    }
+                    match BluetoothEvent::from_with_resolver(message, &self.resolve_uuid) {
+                        Some(BluetoothEvent::None) | None => continue,
+                        event => return event,
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<'a> Drop for BluetoothEventStream<'a> {
+    fn drop(&mut self) {
+        let _ = self
+            .session
+            .get_connection()
+            .remove_match(PROPERTIES_CHANGED_RULE);
+    }
+}
+
+#[cfg(feature = "async")]
+mod stream {
+    use super::BluetoothEventStream;
+    use crate::bluetooth_event::BluetoothEvent;
+    use futures::stream::Stream;
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+    use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver};
+
+    /// A `futures::Stream` of `BluetoothEvent`s usable under tokio.
+    ///
+    /// The blocking [`BluetoothEventStream`] iterator runs on a dedicated
+    /// `spawn_blocking` task and forwards events over a channel, the way
+    /// `bluez-async` keeps its channel drained, so the reactor thread is never
+    /// blocked on the D-Bus message pump.
+    pub struct BluetoothEventAsyncStream {
+        receiver: UnboundedReceiver<BluetoothEvent>,
+    }
+
+    impl BluetoothEventStream<'static> {
+        /// Drive this stream on a blocking task and surface its events as a
+        /// `futures::Stream`.
+        pub fn into_stream(self) -> BluetoothEventAsyncStream {
+            let (sender, receiver) = unbounded_channel();
+            tokio::task::spawn_blocking(move || {
+                let mut events = self;
+                while let Some(event) = events.next() {
+                    if sender.send(event).is_err() {
+                        break;
+                    }
+                }
+            });
+            BluetoothEventAsyncStream { receiver }
+        }
+    }
+
+    impl Stream for BluetoothEventAsyncStream {
+        type Item = BluetoothEvent;
+
+        fn poll_next(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+        ) -> Poll<Option<BluetoothEvent>> {
+            self.receiver.poll_recv(cx)
+        }
+    }
+}